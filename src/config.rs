@@ -0,0 +1,99 @@
+// Copyright (c) 2023 The TQUIC Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::multipath_scheduler::MultipathAlgorithm;
+use crate::multipath_scheduler::MultipathSchedulerFactory;
+use crate::recovery::RecoveryConfig;
+use crate::Result;
+
+/// Configuration for multipath scheduling.
+pub struct MultipathConfig {
+    /// The built-in scheduling algorithm to use when no custom scheduler has
+    /// been installed via [`MultipathConfig::set_custom_scheduler`].
+    pub multipath_algor: MultipathAlgorithm,
+
+    /// A factory for an application-supplied [`MultipathScheduler`], if one
+    /// has been installed. Takes precedence over `multipath_algor`.
+    ///
+    /// [`MultipathScheduler`]: crate::multipath_scheduler::MultipathScheduler
+    pub(crate) custom_scheduler: Option<MultipathSchedulerFactory>,
+
+    /// Initial value of the BLEST adaptive safety margin `lambda`, in units
+    /// of path MSS.
+    pub blest_lambda_init: f64,
+
+    /// Lower bound for the BLEST adaptive safety margin.
+    pub blest_lambda_min: f64,
+
+    /// Upper bound for the BLEST adaptive safety margin.
+    pub blest_lambda_max: f64,
+
+    /// CE-marked fraction above which a path is considered ECN-congested by
+    /// the `MinRtt`, `Redundant`, `Ecf` and `Blest` schedulers.
+    pub ecn_ce_threshold: f64,
+
+    /// How long a path that crossed `ecn_ce_threshold` keeps counting as
+    /// congested after its most recent CE mark, so a single sparse CE mark
+    /// does not flip `is_ecn_congested` back and forth every packet.
+    pub ecn_backoff: std::time::Duration,
+
+    /// Cap, as a fraction of total bytes sent, on how much `Reinject` will
+    /// reinject onto an alternate path.
+    pub reinject_max_fraction: f64,
+}
+
+impl Default for MultipathConfig {
+    fn default() -> Self {
+        MultipathConfig {
+            multipath_algor: MultipathAlgorithm::MinRtt,
+            custom_scheduler: None,
+            blest_lambda_init: 1.0,
+            blest_lambda_min: 0.1,
+            blest_lambda_max: 4.0,
+            ecn_ce_threshold: 0.05,
+            ecn_backoff: std::time::Duration::from_millis(1000),
+            reinject_max_fraction: 0.1,
+        }
+    }
+}
+
+impl MultipathConfig {
+    /// Installs an application-supplied scheduler factory, overriding
+    /// `multipath_algor`.
+    ///
+    /// The factory is called once per connection, since each connection
+    /// needs its own scheduler state.
+    pub fn set_custom_scheduler(&mut self, factory: MultipathSchedulerFactory) {
+        self.custom_scheduler = Some(factory);
+    }
+}
+
+/// Top-level connection configuration.
+pub struct Config {
+    /// Loss-recovery and congestion-control configuration.
+    pub recovery: RecoveryConfig,
+
+    /// Multipath scheduling configuration.
+    pub multipath: MultipathConfig,
+}
+
+impl Config {
+    /// Creates a configuration populated with default values.
+    pub fn new() -> Result<Config> {
+        Ok(Config {
+            recovery: RecoveryConfig::default(),
+            multipath: MultipathConfig::default(),
+        })
+    }
+}
@@ -0,0 +1,44 @@
+// Copyright (c) 2023 The TQUIC Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub mod path;
+pub mod space;
+pub mod stream;
+
+use stream::StreamMap;
+
+/// The per-connection state consulted by a [`crate::MultipathScheduler`]:
+/// its streams (for path affinity and sendable bytes).
+///
+/// This is intentionally minimal -- it exists to give applications a
+/// connection-level entry point for the state schedulers read, not to
+/// model the full QUIC connection lifecycle.
+pub struct Connection {
+    pub(crate) streams: StreamMap,
+}
+
+impl Connection {
+    /// Wraps an existing `StreamMap` as a `Connection`.
+    pub fn new(streams: StreamMap) -> Connection {
+        Connection { streams }
+    }
+
+    /// Pins `stream_id` to `path_id`, so that schedulers prefer sending its
+    /// data on that path over their normal selection logic. The
+    /// connection-level entry point for
+    /// [`StreamMap::set_stream_path_affinity`], which this delegates to.
+    pub fn set_stream_path_affinity(&mut self, stream_id: u64, path_id: usize) {
+        self.streams.set_stream_path_affinity(stream_id, path_id);
+    }
+}
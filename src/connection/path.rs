@@ -0,0 +1,182 @@
+// Copyright (c) 2023 The TQUIC Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::recovery::Recovery;
+use crate::recovery::RecoveryConfig;
+use crate::Error;
+use crate::Result;
+
+/// A network path between a local and a peer address.
+///
+/// A connection may have more than one path once multipath is negotiated and
+/// additional paths have been validated.
+#[derive(Debug, Clone)]
+pub struct Path {
+    local_addr: SocketAddr,
+    remote_addr: SocketAddr,
+    is_initial: bool,
+    active: bool,
+    validated: bool,
+
+    /// Sequence number of the destination connection ID assigned to this
+    /// path, if one has been assigned yet.
+    pub dcid_seq: Option<u64>,
+
+    /// Loss-recovery and congestion-control state for this path.
+    pub recovery: Recovery,
+
+    /// CE-marked fraction last observed on this path, held at its most
+    /// recent value until `ecn_congested_until` elapses.
+    ecn_ce_fraction: f64,
+
+    /// Deadline until which `ecn_ce_fraction` keeps reporting congestion
+    /// after the most recent CE mark, per `MultipathConfig::ecn_backoff`.
+    ecn_congested_until: Option<Instant>,
+}
+
+impl Path {
+    /// Creates a new path.
+    ///
+    /// The initial path of a connection is considered validated from the
+    /// start; any path added afterwards must complete path validation before
+    /// it can be used to send non-probing packets.
+    pub fn new(
+        local_addr: SocketAddr,
+        remote_addr: SocketAddr,
+        is_initial: bool,
+        recovery_config: &RecoveryConfig,
+        _server_name: &str,
+    ) -> Path {
+        Path {
+            local_addr,
+            remote_addr,
+            is_initial,
+            active: is_initial,
+            validated: is_initial,
+            dcid_seq: None,
+            recovery: Recovery::new(recovery_config),
+            ecn_ce_fraction: 0.0,
+            ecn_congested_until: None,
+        }
+    }
+
+    /// Returns whether this path has completed path validation.
+    pub fn validated(&self) -> bool {
+        self.validated
+    }
+
+    /// Marks this path as validated.
+    pub fn set_validated(&mut self, validated: bool) {
+        self.validated = validated;
+    }
+
+    /// Returns whether this path is currently active, i.e. usable for
+    /// sending.
+    pub fn active(&self) -> bool {
+        self.active
+    }
+
+    /// Marks this path as active or inactive.
+    pub fn set_active(&mut self, active: bool) {
+        self.active = active;
+    }
+
+    /// Records that an ACK for a packet sent on this path reported the ECN
+    /// CE (Congestion Experienced) codepoint, and arms `backoff` so this
+    /// path keeps reporting as CE-congested for at least that long after the
+    /// most recent mark, rather than flipping back to uncongested as soon as
+    /// a single non-CE ack arrives.
+    pub fn record_ecn_ce_mark(&mut self, fraction: f64, now: Instant, backoff: Duration) {
+        self.ecn_ce_fraction = fraction;
+        self.ecn_congested_until = Some(now + backoff);
+    }
+
+    /// CE-marked fraction most recently observed on this path, or `0.0` once
+    /// the backoff window armed by the last `record_ecn_ce_mark` call has
+    /// elapsed.
+    pub fn ecn_ce_fraction(&self) -> f64 {
+        match self.ecn_congested_until {
+            Some(until) if Instant::now() < until => self.ecn_ce_fraction,
+            _ => 0.0,
+        }
+    }
+}
+
+/// The collection of paths associated with a connection.
+pub struct PathMap {
+    paths: Vec<Path>,
+    multipath: bool,
+    #[allow(dead_code)]
+    max_paths: usize,
+}
+
+impl PathMap {
+    /// Creates a `PathMap` seeded with the connection's initial path.
+    pub fn new(initial_path: Path, max_paths: usize, multipath: bool) -> PathMap {
+        PathMap {
+            paths: vec![initial_path],
+            multipath,
+            max_paths,
+        }
+    }
+
+    /// Enables multipath, allowing more than one path to be active at once.
+    pub fn enable_multipath(&mut self) {
+        self.multipath = true;
+    }
+
+    /// Returns whether multipath is enabled.
+    pub fn multipath_enabled(&self) -> bool {
+        self.multipath
+    }
+
+    /// Returns the number of paths tracked, including inactive ones.
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Returns whether there are no paths at all.
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Inserts a new path, returning its assigned id.
+    pub fn insert_path(&mut self, path: Path) -> Result<usize> {
+        let path_id = self.paths.len();
+        self.paths.push(path);
+        Ok(path_id)
+    }
+
+    /// Returns a reference to the path with the given id.
+    pub fn get(&self, path_id: usize) -> Result<&Path> {
+        self.paths.get(path_id).ok_or(Error::Done)
+    }
+
+    /// Returns a mutable reference to the path with the given id.
+    pub fn get_mut(&mut self, path_id: usize) -> Result<&mut Path> {
+        self.paths.get_mut(path_id).ok_or(Error::Done)
+    }
+
+    /// Iterates over all active paths, yielding `(path_id, &Path)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Path)> {
+        self.paths
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.active())
+    }
+}
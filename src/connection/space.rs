@@ -0,0 +1,47 @@
+// Copyright (c) 2023 The TQUIC Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// The packet number spaces (Initial, Handshake, Application data) tracked
+/// for a connection.
+#[derive(Debug, Default)]
+pub struct PacketNumSpaceMap {}
+
+impl PacketNumSpaceMap {
+    /// Creates an empty set of packet number spaces.
+    pub fn new() -> PacketNumSpaceMap {
+        PacketNumSpaceMap {}
+    }
+}
+
+/// Bookkeeping kept for a packet after it has been sent, until it is
+/// acknowledged or declared lost.
+#[derive(Debug, Clone)]
+pub struct SentPacket {
+    /// The packet number, unique within its packet number space.
+    pub pkt_num: u64,
+
+    /// The encoded size of the packet, in bytes.
+    pub size: usize,
+
+    /// Whether the packet carries one or more STREAM frames.
+    pub has_stream_frames: bool,
+}
+
+impl SentPacket {
+    /// Returns whether this packet carries application stream data, i.e. is
+    /// a candidate for reinjection onto another path if it stalls.
+    pub fn has_stream_data(&self) -> bool {
+        self.has_stream_frames
+    }
+}
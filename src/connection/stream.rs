@@ -0,0 +1,149 @@
+// Copyright (c) 2023 The TQUIC Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use rustc_hash::FxHashMap;
+
+use crate::TransportParams;
+
+/// The subset of the negotiated transport parameters that affect stream flow
+/// control, copied out so `StreamMap` does not need to borrow the full
+/// `TransportParams`.
+#[derive(Debug, Clone, Default)]
+pub struct StreamTransportParams {
+    initial_max_stream_data_bidi_local: u64,
+    initial_max_stream_data_bidi_remote: u64,
+}
+
+impl From<&TransportParams> for StreamTransportParams {
+    fn from(params: &TransportParams) -> Self {
+        StreamTransportParams {
+            initial_max_stream_data_bidi_local: params.initial_max_stream_data_bidi_local,
+            initial_max_stream_data_bidi_remote: params.initial_max_stream_data_bidi_remote,
+        }
+    }
+}
+
+/// The collection of streams associated with a connection, and the
+/// connection-level flow-control and scheduling state shared across them.
+pub struct StreamMap {
+    #[allow(dead_code)]
+    is_server: bool,
+
+    local_max_data: u64,
+    send_window_used: u64,
+    sendable_bytes: u64,
+
+    /// Streams with a path affinity hint, keyed by stream id, set via
+    /// [`StreamMap::set_stream_path_affinity`].
+    path_affinity: FxHashMap<u64, usize>,
+
+    /// Stream ids with data ready to send, in the order they should be
+    /// offered to the scheduler.
+    sendable: Vec<u64>,
+
+    /// Bytes still queued to send for each stream in `sendable`. Source of
+    /// truth for `sendable_bytes`, which is just this map's total.
+    queued: FxHashMap<u64, u64>,
+
+    #[allow(dead_code)]
+    params: StreamTransportParams,
+}
+
+impl StreamMap {
+    /// Creates an empty `StreamMap`.
+    pub fn new(
+        is_server: bool,
+        local_max_data: u64,
+        _peer_max_data: u64,
+        params: StreamTransportParams,
+    ) -> StreamMap {
+        StreamMap {
+            is_server,
+            local_max_data,
+            send_window_used: 0,
+            sendable_bytes: 0,
+            path_affinity: FxHashMap::default(),
+            sendable: Vec::new(),
+            queued: FxHashMap::default(),
+            params,
+        }
+    }
+
+    /// Queues `len` bytes of new data to send on `stream_id`, adding it to
+    /// the sendable set if it wasn't already in it. Schedulers observe the
+    /// result through `sendable_bytes`, `send_window_available` and
+    /// `peek_sendable_stream_id`.
+    pub fn queue_stream_data(&mut self, stream_id: u64, len: u64) {
+        if len == 0 {
+            return;
+        }
+        if !self.sendable.contains(&stream_id) {
+            self.sendable.push(stream_id);
+        }
+        *self.queued.entry(stream_id).or_insert(0) += len;
+        self.sendable_bytes += len;
+    }
+
+    /// Records that `len` bytes previously queued on `stream_id` were sent,
+    /// consuming both that stream's queued bytes and the connection-level
+    /// send window. Removes the stream from the sendable set once its
+    /// queue is empty.
+    pub fn on_stream_data_sent(&mut self, stream_id: u64, len: u64) {
+        if let Some(remaining) = self.queued.get_mut(&stream_id) {
+            let sent = len.min(*remaining);
+            *remaining -= sent;
+            self.sendable_bytes = self.sendable_bytes.saturating_sub(sent);
+            if *remaining == 0 {
+                self.queued.remove(&stream_id);
+                self.sendable.retain(|&id| id != stream_id);
+            }
+        }
+        self.send_window_used += len;
+    }
+
+    /// Connection-level send window not yet consumed by stream data sent so
+    /// far (acknowledged or not). BLEST uses this to estimate whether routing more data
+    /// onto a slow path risks exhausting the window before the fast path's
+    /// cwnd reopens.
+    pub fn send_window_available(&self) -> u64 {
+        self.local_max_data.saturating_sub(self.send_window_used)
+    }
+
+    /// Returns the id of the next stream with data ready to send, without
+    /// removing it from the sendable set.
+    pub fn peek_sendable_stream_id(&self) -> Option<u64> {
+        self.sendable.first().copied()
+    }
+
+    /// Pins `stream_id` to `path_id`, so that schedulers prefer sending its
+    /// data on that path over their normal selection logic. Useful for e.g.
+    /// a latency-sensitive control stream that should avoid sharing a
+    /// bulk-transfer path.
+    pub fn set_stream_path_affinity(&mut self, stream_id: u64, path_id: usize) {
+        self.path_affinity.insert(stream_id, path_id);
+    }
+
+    /// Returns the path affinity hint for `stream_id`, if one was set via
+    /// [`StreamMap::set_stream_path_affinity`].
+    pub fn stream_path_affinity(&self, stream_id: u64) -> Option<usize> {
+        self.path_affinity.get(&stream_id).copied()
+    }
+
+    /// Total bytes currently ready to send across all streams. Used by the
+    /// `Ecf` scheduler to estimate how long a path would take to drain the
+    /// queue.
+    pub fn sendable_bytes(&self) -> u64 {
+        self.sendable_bytes
+    }
+}
@@ -0,0 +1,40 @@
+// Copyright (c) 2023 The TQUIC Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+/// A specialized [`std::result::Result`] type for this crate's operations.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// An error that occurred while processing a QUIC connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// There is no more work to do, or no viable candidate was found for the
+    /// requested operation.
+    Done,
+
+    /// A configuration value was invalid.
+    InvalidConfig(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Done => write!(f, "done"),
+            Error::InvalidConfig(reason) => write!(f, "invalid config: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,31 @@
+// Copyright (c) 2023 The TQUIC Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod config;
+mod error;
+mod recovery;
+mod transport_params;
+
+pub mod connection;
+pub mod multipath_scheduler;
+
+pub use config::Config;
+pub use config::MultipathConfig;
+pub use connection::path::Path;
+pub use error::Error;
+pub use error::Result;
+pub use multipath_scheduler::MultipathAlgorithm;
+pub use multipath_scheduler::MultipathScheduler;
+pub use multipath_scheduler::MultipathSchedulerFactory;
+pub use transport_params::TransportParams;
@@ -15,10 +15,14 @@
 #![allow(unused_variables)]
 
 use core::str::FromStr;
+use std::sync::Arc;
 use std::time::Instant;
 
+use self::scheduler_blest::*;
+use self::scheduler_ecf::*;
 use self::scheduler_minrtt::*;
 use self::scheduler_redundant::*;
+use self::scheduler_reinject::*;
 use crate::connection::path::PathMap;
 use crate::connection::space::PacketNumSpaceMap;
 use crate::connection::space::SentPacket;
@@ -29,9 +33,15 @@ use crate::Result;
 
 /// MultipathScheduler is a packet scheduler that decides the path over which
 /// the next QUIC packet will be sent.
+///
+/// Applications that need a policy not covered by [`MultipathAlgorithm`]
+/// (e.g. cost-aware cellular-vs-wifi preference, or deadline-aware
+/// scheduling) can implement this trait and install it via
+/// [`MultipathConfig::set_custom_scheduler`].
+///
 /// Note: The API of MultipathScheduler is not stable and may change in future
 /// versions.
-pub(crate) trait MultipathScheduler {
+pub trait MultipathScheduler {
     /// Select a validated path with sufficient congestion window for sending
     /// non-probing packets.
     fn on_select(
@@ -52,8 +62,29 @@ pub(crate) trait MultipathScheduler {
         streams: &mut StreamMap,
     ) {
     }
+
+    /// Process an acknowledgment or loss-detection feedback event for a
+    /// previously sent packet. `lost` is true when the packet was declared
+    /// lost; it is false when the packet was acknowledged.
+    fn on_ack(
+        &mut self,
+        packet: &SentPacket,
+        lost: bool,
+        now: Instant,
+        path_id: usize,
+        paths: &mut PathMap,
+        spaces: &mut PacketNumSpaceMap,
+        streams: &mut StreamMap,
+    ) {
+    }
 }
 
+/// Factory used to build a fresh [`MultipathScheduler`] for each connection
+/// that is configured with a custom scheduling policy. It is a factory,
+/// rather than a shared scheduler instance, because each connection needs
+/// its own scheduler state (e.g. `EcfScheduler::waiting`).
+pub type MultipathSchedulerFactory = Arc<dyn Fn() -> Box<dyn MultipathScheduler> + Send + Sync>;
+
 /// Available multipath scheduling algorithm
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MultipathAlgorithm {
@@ -71,6 +102,25 @@ pub enum MultipathAlgorithm {
     /// present, it ensures a goodput at least equivalent to the best single
     /// path.
     Redundant,
+
+    /// The scheduler sends packets over the path with the lowest smoothed RTT
+    /// unless a slower path is estimated to finish sending the currently
+    /// queued data sooner than waiting for the fastest path's congestion
+    /// window to reopen. It targets heterogeneous networks where MinRtt
+    /// would otherwise waste the fastest path's send window.
+    Ecf,
+
+    /// The scheduler avoids sending on a slower path when doing so would
+    /// exhaust the connection-level send window and stall the fastest path,
+    /// which would otherwise cause receiver-side head-of-line blocking
+    /// across streams under flow control.
+    Blest,
+
+    /// The scheduler behaves like MinRtt, but when data sent on a path
+    /// appears stalled (declared lost, or RTT-elapsed without ack), it
+    /// selectively reinjects that data's retransmission onto the best
+    /// alternate validated path instead of always duplicating every packet.
+    Reinject,
 }
 
 impl FromStr for MultipathAlgorithm {
@@ -81,17 +131,67 @@ impl FromStr for MultipathAlgorithm {
             Ok(MultipathAlgorithm::MinRtt)
         } else if algor.eq_ignore_ascii_case("redundant") {
             Ok(MultipathAlgorithm::Redundant)
+        } else if algor.eq_ignore_ascii_case("ecf") {
+            Ok(MultipathAlgorithm::Ecf)
+        } else if algor.eq_ignore_ascii_case("blest") {
+            Ok(MultipathAlgorithm::Blest)
+        } else if algor.eq_ignore_ascii_case("reinject") {
+            Ok(MultipathAlgorithm::Reinject)
         } else {
             Err(Error::InvalidConfig("unknown".into()))
         }
     }
 }
 
+/// Check whether the next stream data to be sent has a path affinity hint
+/// (set via [`StreamMap::set_stream_path_affinity`]) that can currently be
+/// honored, i.e. the preferred path is validated and has cwnd available.
+///
+/// All built-in schedulers consult this before falling back to their normal
+/// path selection logic, so that pinning a stream (e.g. a latency-sensitive
+/// control stream) to a path takes precedence over the scheduling algorithm.
+pub(crate) fn affinity_path(paths: &PathMap, streams: &StreamMap) -> Option<usize> {
+    let stream_id = streams.peek_sendable_stream_id()?;
+    let path_id = streams.stream_path_affinity(stream_id)?;
+    let path = paths.get(path_id).ok()?;
+    if path.validated() && path.recovery.cwnd_available() > 0 {
+        Some(path_id)
+    } else {
+        None
+    }
+}
+
+/// Check whether a path is currently considered ECN-congested, i.e. the
+/// CE-marked fraction last passed to `Path::record_ecn_ce_mark` exceeds
+/// `ce_threshold`. That fraction is computed by the caller; no sliding
+/// window is kept or configured here. A path that crossed the threshold stays congested until
+/// `Path::ecn_ce_fraction` itself reflects the configured backoff window
+/// (`conf.ecn_backoff`), so callers only need to compare against the
+/// threshold here.
+///
+/// Built-in schedulers deprioritize (MinRtt) or drop (Redundant) a
+/// CE-congested path even when it otherwise looks like the best candidate,
+/// so that the endpoint reacts to ECN feedback the same way congestion
+/// control does for a single path.
+pub(crate) fn is_ecn_congested(path_id: usize, paths: &PathMap, ce_threshold: f64) -> bool {
+    let Ok(path) = paths.get(path_id) else {
+        return false;
+    };
+    path.ecn_ce_fraction() > ce_threshold
+}
+
 /// Build a multipath scheduler
 pub(crate) fn build_multipath_scheduler(conf: &MultipathConfig) -> Box<dyn MultipathScheduler> {
+    if let Some(custom_scheduler) = &conf.custom_scheduler {
+        return custom_scheduler();
+    }
+
     match conf.multipath_algor {
         MultipathAlgorithm::MinRtt => Box::new(MinRttScheduler::new(conf)),
         MultipathAlgorithm::Redundant => Box::new(RedundantScheduler::new(conf)),
+        MultipathAlgorithm::Ecf => Box::new(EcfScheduler::new(conf)),
+        MultipathAlgorithm::Blest => Box::new(BlestScheduler::new(conf)),
+        MultipathAlgorithm::Reinject => Box::new(ReinjectScheduler::new(conf)),
     }
 }
 
@@ -99,6 +199,9 @@ pub(crate) fn reinjection_required(algor: MultipathAlgorithm) -> bool {
     match algor {
         MultipathAlgorithm::MinRtt => false,
         MultipathAlgorithm::Redundant => true,
+        MultipathAlgorithm::Ecf => false,
+        MultipathAlgorithm::Blest => false,
+        MultipathAlgorithm::Reinject => true,
     }
 }
 
@@ -155,6 +258,13 @@ pub(crate) mod tests {
             path.set_active(active);
             Ok(())
         }
+
+        /// Queue `len` bytes of data to send on `stream_id`, so
+        /// `StreamMap::sendable_bytes`/`send_window_available` reflect it
+        /// the way a scheduler under test would actually see.
+        pub(crate) fn queue_stream_data(&mut self, stream_id: u64, len: u64) {
+            self.streams.queue_stream_data(stream_id, len);
+        }
     }
 
     fn new_test_path(local: &str, remote: &str, is_initial: bool, initial_rtt: u64) -> Path {
@@ -176,6 +286,12 @@ pub(crate) mod tests {
             ("redundant", Ok(MultipathAlgorithm::Redundant)),
             ("Redundant", Ok(MultipathAlgorithm::Redundant)),
             ("REDUNDANT", Ok(MultipathAlgorithm::Redundant)),
+            ("ecf", Ok(MultipathAlgorithm::Ecf)),
+            ("ECF", Ok(MultipathAlgorithm::Ecf)),
+            ("blest", Ok(MultipathAlgorithm::Blest)),
+            ("BLEST", Ok(MultipathAlgorithm::Blest)),
+            ("reinject", Ok(MultipathAlgorithm::Reinject)),
+            ("REINJECT", Ok(MultipathAlgorithm::Reinject)),
             ("redun", Err(Error::InvalidConfig("unknown".into()))),
         ];
 
@@ -185,5 +301,8 @@ pub(crate) mod tests {
     }
 }
 
+mod scheduler_blest;
+mod scheduler_ecf;
 mod scheduler_minrtt;
 mod scheduler_redundant;
+mod scheduler_reinject;
@@ -0,0 +1,191 @@
+// Copyright (c) 2023 The TQUIC Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::MultipathScheduler;
+use crate::connection::path::PathMap;
+use crate::connection::space::PacketNumSpaceMap;
+use crate::connection::stream::StreamMap;
+use crate::Error;
+use crate::MultipathConfig;
+use crate::Result;
+
+/// How much the adaptive `lambda` factor grows when the fast path is found
+/// to have become cwnd-blocked shortly after a slow-path send, and how much
+/// it decays otherwise.
+const LAMBDA_GROWTH: f64 = 0.5;
+const LAMBDA_DECAY: f64 = 0.01;
+
+/// BLEST (Blocking Estimation) scheduler.
+///
+/// BLEST avoids scheduling on a slow path when doing so would exhaust the
+/// connection-level send window and stall the fastest path, which would
+/// otherwise cause receiver-side head-of-line blocking across streams. See
+/// "Do Not Cross the Line! Single Round-Trip Time Mitigation For HOL-blocking
+/// in Multipath Transport" for the original BLEST formulation.
+pub(crate) struct BlestScheduler {
+    /// Adaptive safety margin (in units of path MSS) added to the estimated
+    /// bytes the fastest path needs, to account for estimation error.
+    lambda: f64,
+
+    /// Lower/upper bounds for `lambda`.
+    lambda_min: f64,
+    lambda_max: f64,
+
+    /// Whether the fastest path was cwnd-blocked on the previous call,
+    /// used to detect that a recent slow-path send caused blocking.
+    fast_was_blocked: bool,
+
+    /// CE-marked fraction above which a path is considered ECN-congested
+    /// and skipped as a candidate.
+    ecn_ce_threshold: f64,
+}
+
+impl BlestScheduler {
+    pub(crate) fn new(conf: &MultipathConfig) -> Self {
+        BlestScheduler {
+            lambda: conf.blest_lambda_init,
+            lambda_min: conf.blest_lambda_min,
+            lambda_max: conf.blest_lambda_max,
+            fast_was_blocked: false,
+            ecn_ce_threshold: conf.ecn_ce_threshold,
+        }
+    }
+
+    fn adapt_lambda(&mut self, fast_blocked_now: bool, slow_path_sent: bool) {
+        if fast_blocked_now && !self.fast_was_blocked && slow_path_sent {
+            self.lambda = (self.lambda + LAMBDA_GROWTH).min(self.lambda_max);
+        } else {
+            self.lambda = (self.lambda - LAMBDA_DECAY).max(self.lambda_min);
+        }
+        self.fast_was_blocked = fast_blocked_now;
+    }
+}
+
+impl MultipathScheduler for BlestScheduler {
+    fn on_select(
+        &mut self,
+        paths: &mut PathMap,
+        _spaces: &mut PacketNumSpaceMap,
+        streams: &mut StreamMap,
+    ) -> Result<usize> {
+        if let Some(path_id) = super::affinity_path(paths, streams) {
+            self.adapt_lambda(false, false);
+            return Ok(path_id);
+        }
+
+        let fastest = paths
+            .iter()
+            .filter(|(_, p)| p.validated())
+            .min_by_key(|(_, p)| p.recovery.rtt_stats.smoothed_rtt())
+            .map(|(id, _)| id)
+            .ok_or(Error::Done)?;
+
+        let f = paths.get(fastest)?;
+        let fast_blocked = f.recovery.cwnd_available() == 0;
+        if !fast_blocked {
+            self.adapt_lambda(false, false);
+            return Ok(fastest);
+        }
+
+        let srtt_f = f.recovery.rtt_stats.smoothed_rtt().as_secs_f64();
+        let cwnd_f = f.recovery.cwnd() as f64;
+
+        for (id, s) in paths.iter() {
+            if id == fastest || !s.validated() || s.recovery.cwnd_available() == 0 {
+                continue;
+            }
+            if super::is_ecn_congested(id, paths, self.ecn_ce_threshold) {
+                continue;
+            }
+
+            let srtt_s = s.recovery.rtt_stats.smoothed_rtt().as_secs_f64();
+            let x = cwnd_f * (srtt_s / srtt_f).ceil();
+            let mss = s.recovery.max_datagram_size() as f64;
+            let budget = streams.send_window_available() as f64;
+
+            if s.recovery.bytes_in_flight() as f64 + x + self.lambda * mss > budget {
+                // Sending on `s` risks exhausting the connection send window
+                // before `f` frees up again; wait for `f` instead.
+                continue;
+            }
+
+            self.adapt_lambda(true, true);
+            return Ok(id);
+        }
+
+        self.adapt_lambda(true, false);
+        Err(Error::Done)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::multipath_scheduler::tests::MultipathTester;
+
+    #[test]
+    fn picks_fastest_path_and_decays_lambda_when_not_blocked() {
+        let mut tester = MultipathTester::new().unwrap();
+        tester.add_path("127.0.0.1:444", "127.0.0.1:8444", 400).unwrap();
+
+        let mut sched = BlestScheduler::new(&MultipathConfig::default());
+        let lambda_before = sched.lambda;
+        let path_id = sched
+            .on_select(&mut tester.paths, &mut tester.spaces, &mut tester.streams)
+            .unwrap();
+        assert_eq!(path_id, 0);
+        assert!(sched.lambda <= lambda_before);
+    }
+
+    #[test]
+    fn uses_slow_path_when_fast_is_blocked_and_budget_allows() {
+        let mut tester = MultipathTester::new().unwrap();
+        tester.add_path("127.0.0.1:444", "127.0.0.1:8444", 250).unwrap();
+        tester.paths.get_mut(1).unwrap().set_validated(true);
+        tester
+            .paths
+            .get_mut(0)
+            .unwrap()
+            .recovery
+            .on_packet_sent(12_000);
+
+        let mut sched = BlestScheduler::new(&MultipathConfig::default());
+        let lambda_before = sched.lambda;
+        let path_id = sched
+            .on_select(&mut tester.paths, &mut tester.spaces, &mut tester.streams)
+            .unwrap();
+        assert_eq!(path_id, 1);
+        assert!(sched.lambda > lambda_before);
+    }
+
+    #[test]
+    fn waits_when_slow_path_would_exhaust_the_send_window() {
+        let mut tester = MultipathTester::new().unwrap();
+        tester.add_path("127.0.0.1:444", "127.0.0.1:8444", 250).unwrap();
+        tester.paths.get_mut(1).unwrap().set_validated(true);
+        tester
+            .paths
+            .get_mut(0)
+            .unwrap()
+            .recovery
+            .on_packet_sent(12_000);
+        // Consume nearly all of the connection-level send window so no
+        // candidate can clear the budget check.
+        tester.streams.on_stream_data_sent(4, 1024 * 1024 - 100);
+
+        let mut sched = BlestScheduler::new(&MultipathConfig::default());
+        let result = sched.on_select(&mut tester.paths, &mut tester.spaces, &mut tester.streams);
+        assert!(matches!(result, Err(Error::Done)));
+    }
+}
@@ -0,0 +1,183 @@
+// Copyright (c) 2023 The TQUIC Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::MultipathScheduler;
+use crate::connection::path::PathMap;
+use crate::connection::space::PacketNumSpaceMap;
+use crate::connection::stream::StreamMap;
+use crate::Error;
+use crate::MultipathConfig;
+use crate::Result;
+
+/// Margin used to damp oscillation between the fastest path and a faster-
+/// looking slow path. Once the scheduler decides to wait for the fastest
+/// path, it keeps waiting until the slow path wins by at least this factor.
+const WAITING_MARGIN: f64 = 1.1;
+
+/// Earliest-Completion-First (ECF) scheduler.
+///
+/// ECF avoids wasting the fastest path's send window by estimating, for the
+/// data currently queued, whether a slower-but-available path would actually
+/// finish sending it sooner than waiting for the fastest path's congestion
+/// window to reopen. See "Is Multi-Path Transport Suitable for Latency
+/// Sensitive Traffic?" for the original ECF formulation.
+pub(crate) struct EcfScheduler {
+    /// Whether the scheduler is currently waiting for the fastest path's
+    /// cwnd to reopen rather than using a slower path.
+    waiting: bool,
+
+    /// CE-marked fraction above which a path is considered ECN-congested
+    /// and deprioritized.
+    ecn_ce_threshold: f64,
+}
+
+impl EcfScheduler {
+    pub(crate) fn new(conf: &MultipathConfig) -> Self {
+        EcfScheduler {
+            waiting: false,
+            ecn_ce_threshold: conf.ecn_ce_threshold,
+        }
+    }
+}
+
+impl MultipathScheduler for EcfScheduler {
+    fn on_select(
+        &mut self,
+        paths: &mut PathMap,
+        _spaces: &mut PacketNumSpaceMap,
+        streams: &mut StreamMap,
+    ) -> Result<usize> {
+        if let Some(path_id) = super::affinity_path(paths, streams) {
+            self.waiting = false;
+            return Ok(path_id);
+        }
+
+        let fastest = paths
+            .iter()
+            .filter(|(id, p)| {
+                p.validated() && !super::is_ecn_congested(*id, paths, self.ecn_ce_threshold)
+            })
+            .min_by_key(|(_, p)| p.recovery.rtt_stats.smoothed_rtt())
+            .map(|(id, _)| id)
+            .or_else(|| {
+                // All validated paths are CE-congested; fall back to the
+                // normal ranking rather than stalling entirely.
+                paths
+                    .iter()
+                    .filter(|(_, p)| p.validated())
+                    .min_by_key(|(_, p)| p.recovery.rtt_stats.smoothed_rtt())
+                    .map(|(id, _)| id)
+            })
+            .ok_or(Error::Done)?;
+
+        let f = paths.get(fastest)?;
+        if f.recovery.cwnd_available() > 0 {
+            self.waiting = false;
+            return Ok(fastest);
+        }
+
+        let n = streams.sendable_bytes() as f64;
+        let srtt_f = f.recovery.rtt_stats.smoothed_rtt().as_secs_f64();
+        let rttvar_f = f.recovery.rtt_stats.rttvar().as_secs_f64();
+        let cwnd_f = f.recovery.cwnd() as f64;
+        let mut x_f = srtt_f * (n / cwnd_f).ceil();
+        x_f *= 1.0 + rttvar_f / srtt_f.max(f64::EPSILON);
+
+        let mut best: Option<(usize, f64)> = None;
+        for (id, s) in paths.iter() {
+            if id == fastest || !s.validated() || s.recovery.cwnd_available() == 0 {
+                continue;
+            }
+
+            let srtt_s = s.recovery.rtt_stats.smoothed_rtt().as_secs_f64();
+            let cwnd_s = s.recovery.cwnd() as f64;
+            let x_s = srtt_s * (n / cwnd_s).ceil();
+
+            if best.map_or(true, |(_, best_x)| x_s < best_x) {
+                best = Some((id, x_s));
+            }
+        }
+
+        let Some((candidate, x_s)) = best else {
+            self.waiting = true;
+            return Err(Error::Done);
+        };
+
+        let threshold = if self.waiting { x_f / WAITING_MARGIN } else { x_f };
+        if x_s < threshold {
+            self.waiting = false;
+            return Ok(candidate);
+        }
+
+        self.waiting = true;
+        Err(Error::Done)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::multipath_scheduler::tests::MultipathTester;
+
+    #[test]
+    fn picks_fastest_path_when_it_has_cwnd_available() {
+        let mut tester = MultipathTester::new().unwrap();
+        tester.add_path("127.0.0.1:444", "127.0.0.1:8444", 400).unwrap();
+        tester.queue_stream_data(4, 50_000);
+
+        let mut sched = EcfScheduler::new(&MultipathConfig::default());
+        let path_id = sched
+            .on_select(&mut tester.paths, &mut tester.spaces, &mut tester.streams)
+            .unwrap();
+        assert_eq!(path_id, 0);
+    }
+
+    #[test]
+    fn switches_to_slow_path_once_fast_path_is_cwnd_blocked() {
+        let mut tester = MultipathTester::new().unwrap();
+        tester.add_path("127.0.0.1:444", "127.0.0.1:8444", 250).unwrap();
+        tester.paths.get_mut(1).unwrap().set_validated(true);
+        tester.queue_stream_data(4, 50_000);
+        tester
+            .paths
+            .get_mut(0)
+            .unwrap()
+            .recovery
+            .on_packet_sent(12_000);
+
+        let mut sched = EcfScheduler::new(&MultipathConfig::default());
+        let path_id = sched
+            .on_select(&mut tester.paths, &mut tester.spaces, &mut tester.streams)
+            .unwrap();
+        assert_eq!(path_id, 1);
+    }
+
+    #[test]
+    fn waits_for_fast_path_when_no_alternate_clears_the_bar() {
+        let mut tester = MultipathTester::new().unwrap();
+        tester.add_path("127.0.0.1:444", "127.0.0.1:8444", 2000).unwrap();
+        tester.paths.get_mut(1).unwrap().set_validated(true);
+        tester.queue_stream_data(4, 50_000);
+        tester
+            .paths
+            .get_mut(0)
+            .unwrap()
+            .recovery
+            .on_packet_sent(12_000);
+
+        let mut sched = EcfScheduler::new(&MultipathConfig::default());
+        let result = sched.on_select(&mut tester.paths, &mut tester.spaces, &mut tester.streams);
+        assert!(matches!(result, Err(Error::Done)));
+    }
+}
@@ -0,0 +1,75 @@
+// Copyright (c) 2023 The TQUIC Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::MultipathScheduler;
+use crate::connection::path::PathMap;
+use crate::connection::space::PacketNumSpaceMap;
+use crate::connection::stream::StreamMap;
+use crate::Error;
+use crate::MultipathConfig;
+use crate::Result;
+
+/// MinRtt scheduler.
+///
+/// Always sends on the validated path with the lowest smoothed RTT. If that
+/// path is currently ECN-congested, it is passed over for the
+/// next-lowest-RTT validated path instead, the same way a single-path
+/// connection's congestion control would react to CE marks; if every
+/// validated path is congested, falls back to the plain lowest-RTT path
+/// rather than stalling.
+pub(crate) struct MinRttScheduler {
+    /// CE-marked fraction above which a path is considered ECN-congested
+    /// and passed over in favor of the next-lowest-RTT path.
+    ecn_ce_threshold: f64,
+}
+
+impl MinRttScheduler {
+    pub(crate) fn new(conf: &MultipathConfig) -> Self {
+        MinRttScheduler {
+            ecn_ce_threshold: conf.ecn_ce_threshold,
+        }
+    }
+}
+
+impl MultipathScheduler for MinRttScheduler {
+    fn on_select(
+        &mut self,
+        paths: &mut PathMap,
+        _spaces: &mut PacketNumSpaceMap,
+        streams: &mut StreamMap,
+    ) -> Result<usize> {
+        if let Some(path_id) = super::affinity_path(paths, streams) {
+            return Ok(path_id);
+        }
+
+        paths
+            .iter()
+            .filter(|(id, p)| {
+                p.validated() && !super::is_ecn_congested(*id, paths, self.ecn_ce_threshold)
+            })
+            .min_by_key(|(_, p)| p.recovery.rtt_stats.smoothed_rtt())
+            .map(|(id, _)| id)
+            .or_else(|| {
+                // Every validated path is CE-congested; fall back to the
+                // plain ranking so the connection doesn't stall entirely
+                // just because nothing looks clean right now.
+                paths
+                    .iter()
+                    .filter(|(_, p)| p.validated())
+                    .min_by_key(|(_, p)| p.recovery.rtt_stats.smoothed_rtt())
+                    .map(|(id, _)| id)
+            })
+            .ok_or(Error::Done)
+    }
+}
@@ -0,0 +1,91 @@
+// Copyright (c) 2023 The TQUIC Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::MultipathScheduler;
+use crate::connection::path::PathMap;
+use crate::connection::space::PacketNumSpaceMap;
+use crate::connection::stream::StreamMap;
+use crate::Error;
+use crate::MultipathConfig;
+use crate::Result;
+
+/// Redundant scheduler.
+///
+/// Sends the same data on every validated path with cwnd available, so that
+/// flow completion time is bounded by the fastest path rather than any one
+/// path's own performance. Since [`MultipathScheduler::on_select`] returns a
+/// single path per call, this scheduler instead hands back one path from the
+/// current redundant set per call, exhausting it before recomputing the
+/// set for the next round. The set is recomputed within the same call that
+/// finds it drained, so no `Err(Error::Done)` marks the end of a round;
+/// `Err(Error::Done)` is returned only when no validated path has cwnd
+/// available.
+///
+/// A path that is currently ECN-congested is dropped from the set entirely
+/// rather than just deprioritized, since duplicating data onto it wastes
+/// bandwidth without improving completion time. If every validated path is
+/// congested, falls back to the full set rather than stalling.
+pub(crate) struct RedundantScheduler {
+    /// CE-marked fraction above which a path is dropped from the redundant
+    /// set.
+    ecn_ce_threshold: f64,
+
+    /// Paths from the current round not yet handed out by `on_select`.
+    /// Recomputed once this drains.
+    pending: Vec<usize>,
+}
+
+impl RedundantScheduler {
+    pub(crate) fn new(conf: &MultipathConfig) -> Self {
+        RedundantScheduler {
+            ecn_ce_threshold: conf.ecn_ce_threshold,
+            pending: Vec::new(),
+        }
+    }
+
+    fn candidates(&self, paths: &PathMap, skip_congested: bool) -> Vec<usize> {
+        paths
+            .iter()
+            .filter(|(id, p)| {
+                p.validated()
+                    && p.recovery.cwnd_available() > 0
+                    && (!skip_congested
+                        || !super::is_ecn_congested(*id, paths, self.ecn_ce_threshold))
+            })
+            .map(|(id, _)| id)
+            .collect()
+    }
+}
+
+impl MultipathScheduler for RedundantScheduler {
+    fn on_select(
+        &mut self,
+        paths: &mut PathMap,
+        _spaces: &mut PacketNumSpaceMap,
+        streams: &mut StreamMap,
+    ) -> Result<usize> {
+        if let Some(path_id) = super::affinity_path(paths, streams) {
+            return Ok(path_id);
+        }
+
+        if self.pending.is_empty() {
+            self.pending = self.candidates(paths, true);
+            if self.pending.is_empty() {
+                self.pending = self.candidates(paths, false);
+            }
+        }
+
+        self.pending.pop().ok_or(Error::Done)
+    }
+}
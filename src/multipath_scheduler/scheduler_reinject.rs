@@ -0,0 +1,398 @@
+// Copyright (c) 2023 The TQUIC Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Instant;
+
+use rustc_hash::FxHashMap;
+
+use super::MultipathScheduler;
+use crate::connection::path::PathMap;
+use crate::connection::space::PacketNumSpaceMap;
+use crate::connection::space::SentPacket;
+use crate::connection::stream::StreamMap;
+use crate::Error;
+use crate::MultipathConfig;
+use crate::Result;
+
+/// Bookkeeping kept for a sent packet that carried stream data, so that it
+/// can be reinjected onto a different path if it stalls.
+struct Inflight {
+    path_id: usize,
+    sent_time: Instant,
+    size: usize,
+}
+
+/// Reinject scheduler.
+///
+/// Behaves like MinRtt for normal path selection, but tracks packets that
+/// carry stream data and, once one of them looks stalled on its original
+/// path (declared lost, or left unacknowledged for longer than that path's
+/// RTT), biases selection toward the best alternate validated path so the
+/// retransmission of that data does not wait behind the same stalled path.
+/// Unlike `Redundant`, this never duplicates data that is still in flight
+/// and on schedule, and the total volume reinjected is capped by
+/// `conf.reinject_max_fraction` of total bytes sent.
+///
+/// Note: `on_ack` must be called for every acked/lost packet on every path
+/// for `stalled_paths` to stay accurate; that wiring belongs in the
+/// connection's packet-acknowledgment handling, alongside the `on_sent` call
+/// already wired in for every packet sent.
+pub(crate) struct ReinjectScheduler {
+    /// Packets carrying stream data that have not yet been acked or lost,
+    /// keyed by (path_id, packet_number).
+    inflight: FxHashMap<(usize, u64), Inflight>,
+
+    /// Paths with data currently believed to be stalled; path selection
+    /// should prefer routing new data away from these when possible.
+    stalled_paths: FxHashMap<usize, Instant>,
+
+    /// Total bytes sent, used to compute the reinjection budget.
+    bytes_sent: u64,
+
+    /// Total bytes reinjected so far.
+    bytes_reinjected: u64,
+
+    /// Cap on reinjected bytes as a fraction of `bytes_sent`.
+    max_fraction: f64,
+}
+
+impl ReinjectScheduler {
+    pub(crate) fn new(conf: &MultipathConfig) -> Self {
+        ReinjectScheduler {
+            inflight: FxHashMap::default(),
+            stalled_paths: FxHashMap::default(),
+            bytes_sent: 0,
+            bytes_reinjected: 0,
+            max_fraction: conf.reinject_max_fraction,
+        }
+    }
+
+    fn budget_available(&self, size: usize) -> bool {
+        let projected = self.bytes_reinjected + size as u64;
+        (projected as f64) <= (self.bytes_sent as f64) * self.max_fraction
+    }
+
+    /// Returns the longest-outstanding in-flight packet on `path_id`, i.e.
+    /// the one whose reinjection is most overdue.
+    fn oldest_stalled_inflight(&self, path_id: usize) -> Option<&Inflight> {
+        self.inflight
+            .values()
+            .filter(|i| i.path_id == path_id)
+            .min_by_key(|i| i.sent_time)
+    }
+
+    /// Sweep in-flight packets for any that have gone unacknowledged for
+    /// longer than their path's RTT, marking the path as stalled.
+    fn sweep_stalled(&mut self, now: Instant, paths: &PathMap) {
+        for ((path_id, _), inflight) in self.inflight.iter() {
+            let Ok(path) = paths.get(*path_id) else {
+                continue;
+            };
+            if now.duration_since(inflight.sent_time) > path.recovery.rtt_stats.smoothed_rtt() {
+                self.stalled_paths.insert(*path_id, now);
+            }
+        }
+    }
+}
+
+impl MultipathScheduler for ReinjectScheduler {
+    fn on_select(
+        &mut self,
+        paths: &mut PathMap,
+        _spaces: &mut PacketNumSpaceMap,
+        streams: &mut StreamMap,
+    ) -> Result<usize> {
+        if let Some(path_id) = super::affinity_path(paths, streams) {
+            return Ok(path_id);
+        }
+
+        let fastest = paths
+            .iter()
+            .filter(|(_, p)| p.validated())
+            .min_by_key(|(_, p)| p.recovery.rtt_stats.smoothed_rtt())
+            .map(|(id, _)| id)
+            .ok_or(Error::Done)?;
+
+        if !self.stalled_paths.contains_key(&fastest) {
+            return Ok(fastest);
+        }
+
+        // The normally-preferred path has stalled data outstanding; reinject
+        // its longest-overdue packet by picking an alternate validated path
+        // with enough cwnd for that specific packet's size, if the
+        // reinjection budget allows it.
+        let Some(stalled) = self.oldest_stalled_inflight(fastest) else {
+            return Ok(fastest);
+        };
+        let size = stalled.size;
+
+        let alternate = paths
+            .iter()
+            .filter(|(id, p)| {
+                *id != fastest && p.validated() && p.recovery.cwnd_available() >= size
+            })
+            .min_by_key(|(_, p)| p.recovery.rtt_stats.smoothed_rtt())
+            .map(|(id, _)| id);
+
+        match alternate {
+            Some(alt) if self.budget_available(size) => Ok(alt),
+            _ => Ok(fastest),
+        }
+    }
+
+    fn on_sent(
+        &mut self,
+        packet: &SentPacket,
+        now: Instant,
+        path_id: usize,
+        paths: &mut PathMap,
+        _spaces: &mut PacketNumSpaceMap,
+        _streams: &mut StreamMap,
+    ) {
+        self.bytes_sent += packet.size as u64;
+        if packet.has_stream_data() {
+            self.inflight.insert(
+                (path_id, packet.pkt_num),
+                Inflight {
+                    path_id,
+                    sent_time: now,
+                    size: packet.size,
+                },
+            );
+        }
+
+        // A send counts against the reinjection budget when it was routed
+        // onto `path_id` specifically to avoid a *different* stalled path,
+        // i.e. some path is stalled and it isn't this one. `on_select`
+        // never knowingly picks a stalled path itself, so checking
+        // `stalled_paths.contains_key(&path_id)` here would almost never
+        // accrue any budget usage.
+        if !self.stalled_paths.is_empty() && !self.stalled_paths.contains_key(&path_id) {
+            self.bytes_reinjected += packet.size as u64;
+        }
+
+        self.sweep_stalled(now, paths);
+    }
+
+    fn on_ack(
+        &mut self,
+        packet: &SentPacket,
+        lost: bool,
+        now: Instant,
+        path_id: usize,
+        paths: &mut PathMap,
+        _spaces: &mut PacketNumSpaceMap,
+        _streams: &mut StreamMap,
+    ) {
+        self.inflight.remove(&(path_id, packet.pkt_num));
+
+        if lost {
+            self.stalled_paths.insert(path_id, now);
+        } else if let Some(stalled_since) = self.stalled_paths.get(&path_id) {
+            // An ack on this path means it is making progress again; clear
+            // the stall once nothing sent before the ack is still pending.
+            if *stalled_since <= now
+                && !self.inflight.values().any(|i| i.path_id == path_id)
+            {
+                self.stalled_paths.remove(&path_id);
+            }
+        }
+
+        self.sweep_stalled(now, paths);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::multipath_scheduler::tests::MultipathTester;
+
+    fn sent_packet(pkt_num: u64, size: usize, has_stream_frames: bool) -> SentPacket {
+        SentPacket {
+            pkt_num,
+            size,
+            has_stream_frames,
+        }
+    }
+
+    #[test]
+    fn picks_fastest_path_when_nothing_is_stalled() {
+        let mut tester = MultipathTester::new().unwrap();
+        tester.add_path("127.0.0.1:444", "127.0.0.1:8444", 400).unwrap();
+        tester.paths.get_mut(1).unwrap().set_validated(true);
+
+        let mut sched = ReinjectScheduler::new(&MultipathConfig::default());
+        let path_id = sched
+            .on_select(&mut tester.paths, &mut tester.spaces, &mut tester.streams)
+            .unwrap();
+        assert_eq!(path_id, 0);
+    }
+
+    #[test]
+    fn reinjects_oldest_outstanding_packet_onto_alternate_path() {
+        let mut tester = MultipathTester::new().unwrap();
+        tester.add_path("127.0.0.1:444", "127.0.0.1:8444", 400).unwrap();
+        tester.paths.get_mut(1).unwrap().set_validated(true);
+
+        let mut sched = ReinjectScheduler::new(&MultipathConfig::default());
+        let now = std::time::Instant::now();
+
+        // Inflate bytes_sent so the reinjection budget has room for the
+        // stalled packet below.
+        for i in 0..20u64 {
+            sched.on_sent(
+                &sent_packet(i, 500, false),
+                now,
+                0,
+                &mut tester.paths,
+                &mut tester.spaces,
+                &mut tester.streams,
+            );
+        }
+
+        sched.on_sent(
+            &sent_packet(100, 500, true),
+            now,
+            0,
+            &mut tester.paths,
+            &mut tester.spaces,
+            &mut tester.streams,
+        );
+        sched.on_sent(
+            &sent_packet(101, 1000, true),
+            now,
+            0,
+            &mut tester.paths,
+            &mut tester.spaces,
+            &mut tester.streams,
+        );
+
+        // Packet 100 is declared lost, marking path 0 stalled; packet 101 is
+        // still outstanding on path 0 and becomes the reinjection candidate.
+        sched.on_ack(
+            &sent_packet(100, 500, true),
+            true,
+            now,
+            0,
+            &mut tester.paths,
+            &mut tester.spaces,
+            &mut tester.streams,
+        );
+
+        let path_id = sched
+            .on_select(&mut tester.paths, &mut tester.spaces, &mut tester.streams)
+            .unwrap();
+        assert_eq!(path_id, 1);
+    }
+
+    #[test]
+    fn falls_back_to_fastest_when_budget_is_exhausted() {
+        let mut tester = MultipathTester::new().unwrap();
+        tester.add_path("127.0.0.1:444", "127.0.0.1:8444", 400).unwrap();
+        tester.paths.get_mut(1).unwrap().set_validated(true);
+
+        let mut sched = ReinjectScheduler::new(&MultipathConfig::default());
+        let now = std::time::Instant::now();
+
+        sched.on_sent(
+            &sent_packet(100, 500, true),
+            now,
+            0,
+            &mut tester.paths,
+            &mut tester.spaces,
+            &mut tester.streams,
+        );
+        sched.on_sent(
+            &sent_packet(101, 1000, true),
+            now,
+            0,
+            &mut tester.paths,
+            &mut tester.spaces,
+            &mut tester.streams,
+        );
+        sched.on_ack(
+            &sent_packet(100, 500, true),
+            true,
+            now,
+            0,
+            &mut tester.paths,
+            &mut tester.spaces,
+            &mut tester.streams,
+        );
+
+        // Only 1500 bytes sent total, so the 10% reinjection budget (150
+        // bytes) cannot cover the 1000-byte stalled packet.
+        let path_id = sched
+            .on_select(&mut tester.paths, &mut tester.spaces, &mut tester.streams)
+            .unwrap();
+        assert_eq!(path_id, 0);
+    }
+
+    #[test]
+    fn falls_back_to_fastest_when_alternate_lacks_cwnd() {
+        let mut tester = MultipathTester::new().unwrap();
+        tester.add_path("127.0.0.1:444", "127.0.0.1:8444", 400).unwrap();
+        tester.paths.get_mut(1).unwrap().set_validated(true);
+        tester
+            .paths
+            .get_mut(1)
+            .unwrap()
+            .recovery
+            .on_packet_sent(11_500);
+
+        let mut sched = ReinjectScheduler::new(&MultipathConfig::default());
+        let now = std::time::Instant::now();
+
+        for i in 0..20u64 {
+            sched.on_sent(
+                &sent_packet(i, 500, false),
+                now,
+                0,
+                &mut tester.paths,
+                &mut tester.spaces,
+                &mut tester.streams,
+            );
+        }
+        sched.on_sent(
+            &sent_packet(100, 500, true),
+            now,
+            0,
+            &mut tester.paths,
+            &mut tester.spaces,
+            &mut tester.streams,
+        );
+        sched.on_sent(
+            &sent_packet(101, 1000, true),
+            now,
+            0,
+            &mut tester.paths,
+            &mut tester.spaces,
+            &mut tester.streams,
+        );
+        sched.on_ack(
+            &sent_packet(100, 500, true),
+            true,
+            now,
+            0,
+            &mut tester.paths,
+            &mut tester.spaces,
+            &mut tester.streams,
+        );
+
+        let path_id = sched
+            .on_select(&mut tester.paths, &mut tester.spaces, &mut tester.streams)
+            .unwrap();
+        assert_eq!(path_id, 0);
+    }
+}
@@ -0,0 +1,114 @@
+// Copyright (c) 2023 The TQUIC Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+/// Configuration for the loss-recovery and congestion-control state created
+/// for each path.
+#[derive(Debug, Clone)]
+pub struct RecoveryConfig {
+    /// RTT used to seed a path's `RttStats` before any sample has been
+    /// taken.
+    pub initial_rtt: Duration,
+}
+
+impl Default for RecoveryConfig {
+    fn default() -> Self {
+        RecoveryConfig {
+            initial_rtt: Duration::from_millis(333),
+        }
+    }
+}
+
+/// Smoothed RTT and RTT variance for a single path, maintained the same way
+/// as the single-path transport (see RFC 9002).
+#[derive(Debug, Clone)]
+pub struct RttStats {
+    smoothed_rtt: Duration,
+    rttvar: Duration,
+}
+
+impl RttStats {
+    fn new(initial_rtt: Duration) -> Self {
+        RttStats {
+            smoothed_rtt: initial_rtt,
+            rttvar: initial_rtt / 2,
+        }
+    }
+
+    /// Returns the current smoothed RTT estimate.
+    pub fn smoothed_rtt(&self) -> Duration {
+        self.smoothed_rtt
+    }
+
+    /// Returns the current RTT variance estimate.
+    pub fn rttvar(&self) -> Duration {
+        self.rttvar
+    }
+}
+
+/// Per-path congestion-control and loss-recovery state.
+#[derive(Debug, Clone)]
+pub struct Recovery {
+    /// RTT statistics for this path.
+    pub rtt_stats: RttStats,
+
+    cwnd: usize,
+    bytes_in_flight: usize,
+    max_datagram_size: usize,
+}
+
+impl Recovery {
+    pub(crate) fn new(conf: &RecoveryConfig) -> Self {
+        Recovery {
+            rtt_stats: RttStats::new(conf.initial_rtt),
+            cwnd: 12_000,
+            bytes_in_flight: 0,
+            max_datagram_size: 1200,
+        }
+    }
+
+    /// Current congestion window, in bytes.
+    pub fn cwnd(&self) -> usize {
+        self.cwnd
+    }
+
+    /// Congestion window not currently occupied by bytes in flight.
+    pub fn cwnd_available(&self) -> usize {
+        self.cwnd.saturating_sub(self.bytes_in_flight)
+    }
+
+    /// Bytes sent on this path that have not yet been acked or declared
+    /// lost.
+    pub fn bytes_in_flight(&self) -> usize {
+        self.bytes_in_flight
+    }
+
+    /// Largest datagram size this path is willing to send.
+    pub fn max_datagram_size(&self) -> usize {
+        self.max_datagram_size
+    }
+
+    /// Records that `size` bytes were sent on this path and are now in
+    /// flight, consuming that much of `cwnd_available`.
+    pub fn on_packet_sent(&mut self, size: usize) {
+        self.bytes_in_flight += size;
+    }
+
+    /// Records that `size` bytes previously in flight on this path were
+    /// acked or declared lost, freeing that much of `cwnd_available`.
+    pub fn on_packet_acked(&mut self, size: usize) {
+        self.bytes_in_flight = self.bytes_in_flight.saturating_sub(size);
+    }
+}
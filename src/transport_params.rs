@@ -0,0 +1,34 @@
+// Copyright (c) 2023 The TQUIC Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// QUIC transport parameters exchanged during the handshake (RFC 9000
+/// Section 18.2).
+#[derive(Debug, Clone)]
+pub struct TransportParams {
+    /// Initial flow-control limit for locally-initiated bidirectional
+    /// streams.
+    pub initial_max_stream_data_bidi_local: u64,
+
+    /// Initial flow-control limit for peer-initiated bidirectional streams.
+    pub initial_max_stream_data_bidi_remote: u64,
+}
+
+impl Default for TransportParams {
+    fn default() -> Self {
+        TransportParams {
+            initial_max_stream_data_bidi_local: 1024 * 1024,
+            initial_max_stream_data_bidi_remote: 1024 * 1024,
+        }
+    }
+}